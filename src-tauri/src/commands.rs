@@ -1,17 +1,16 @@
-use crate::DbConnection;
-use crate::{Chat, FileAttachment, Message};
+use crate::DbPool;
+use crate::{Chat, FileAttachment, Message, MessageSearchResult};
 use chrono::Utc;
-use rusqlite::Connection;
 use rusqlite::{params, OptionalExtension, Transaction};
 use std::collections::HashMap;
-use tauri::{Manager, State};
+use tauri::State;
 use uuid::Uuid;
 
 #[tauri::command]
-pub async fn create_chat(conn: State<'_, DbConnection>, title: String) -> Result<Chat, String> {
+pub async fn create_chat(pool: State<'_, DbPool>, title: String) -> Result<Chat, String> {
     println!("📝 create_chat called with title: {}", title);
 
-    let conn = conn.0.lock().unwrap();
+    let conn = pool.get().map_err(|e| e.to_string())?;
     let now = Utc::now();
     let chat = Chat {
         id: Uuid::new_v4().to_string(),
@@ -32,7 +31,7 @@ pub async fn create_chat(conn: State<'_, DbConnection>, title: String) -> Result
 
 #[tauri::command]
 pub async fn add_message(
-    conn: State<'_, DbConnection>,
+    pool: State<'_, DbPool>,
     chat_id: String,
     content: String,
     role: String,
@@ -43,7 +42,7 @@ pub async fn add_message(
         chat_id, content, role
     );
 
-    let mut conn = conn.0.lock().unwrap();
+    let mut conn = pool.get().map_err(|e| e.to_string())?;
     let tx = conn.transaction().map_err(|e| e.to_string())?;
 
     let message = Message {
@@ -98,10 +97,10 @@ pub async fn add_message(
 }
 
 #[tauri::command]
-pub async fn get_chats(conn: State<'_, DbConnection>) -> Result<Vec<Chat>, String> {
+pub async fn get_chats(pool: State<'_, DbPool>) -> Result<Vec<Chat>, String> {
     println!("📋 get_chats called");
 
-    let conn = conn.0.lock().unwrap();
+    let conn = pool.get().map_err(|e| e.to_string())?;
     let mut stmt = conn
         .prepare("SELECT * FROM chats ORDER BY updated_at DESC")
         .map_err(|e| e.to_string())?;
@@ -125,12 +124,12 @@ pub async fn get_chats(conn: State<'_, DbConnection>) -> Result<Vec<Chat>, Strin
 
 #[tauri::command]
 pub async fn get_messages(
-    conn: State<'_, DbConnection>,
+    pool: State<'_, DbPool>,
     chat_id: String,
 ) -> Result<Vec<Message>, String> {
     println!("📨 get_messages called for chat_id: {}", chat_id);
 
-    let conn = conn.0.lock().unwrap();
+    let conn = pool.get().map_err(|e| e.to_string())?;
 
     // First, verify the chat exists
     let chat_exists: bool = conn
@@ -145,14 +144,14 @@ pub async fn get_messages(
     let mut stmt = conn
         .prepare(
             "SELECT m.id, m.chat_id, m.content, m.role, m.created_at,
-                COALESCE(a.id, '') as attachment_id, 
-                COALESCE(a.name, '') as name, 
+                COALESCE(a.id, '') as attachment_id,
+                COALESCE(a.name, '') as name,
                 COALESCE(a.content, '') as attachment_content,
                 COALESCE(a.type, '') as type,
                 COALESCE(a.size, 0) as size
          FROM messages m
          LEFT JOIN attachments a ON m.id = a.message_id
-         WHERE m.chat_id = ?1 
+         WHERE m.chat_id = ?1
          ORDER BY m.created_at ASC",
         )
         .map_err(|e| e.to_string())?;
@@ -207,12 +206,11 @@ pub async fn get_messages(
 
 #[tauri::command]
 pub async fn save_api_key(
+    pool: State<'_, DbPool>,
     key_type: String,
     key_value: String,
-    app: tauri::AppHandle,
 ) -> Result<(), String> {
-    let conn = Connection::open(app.path().app_data_dir().unwrap().join("chats.db"))
-        .map_err(|e| e.to_string())?;
+    let conn = pool.get().map_err(|e| e.to_string())?;
 
     conn.execute(
         "INSERT OR REPLACE INTO api_keys (key_type, key_value) VALUES (?1, ?2)",
@@ -224,9 +222,8 @@ pub async fn save_api_key(
 }
 
 #[tauri::command]
-pub async fn get_api_keys(app: tauri::AppHandle) -> Result<HashMap<String, String>, String> {
-    let conn = Connection::open(app.path().app_data_dir().unwrap().join("chats.db"))
-        .map_err(|e| e.to_string())?;
+pub async fn get_api_keys(pool: State<'_, DbPool>) -> Result<HashMap<String, String>, String> {
+    let conn = pool.get().map_err(|e| e.to_string())?;
 
     let mut stmt = conn
         .prepare("SELECT key_type, key_value FROM api_keys")
@@ -241,15 +238,66 @@ pub async fn get_api_keys(app: tauri::AppHandle) -> Result<HashMap<String, Strin
     Ok(keys)
 }
 
+#[tauri::command]
+pub async fn search_messages(
+    pool: State<'_, DbPool>,
+    query: String,
+    limit: i64,
+) -> Result<Vec<MessageSearchResult>, String> {
+    println!("🔍 search_messages called with query: {}", query);
+
+    let conn = pool.get().map_err(|e| e.to_string())?;
+
+    // `query` is passed straight through to FTS5's MATCH, so callers get prefix queries
+    // ("rust*") and phrase queries ("\"exact phrase\"") for free. Message-body matches and
+    // chat-title matches are unioned so a chat found only by its title still surfaces,
+    // ranked alongside message hits by the same bm25 score.
+    let mut stmt = conn
+        .prepare(
+            "SELECT m.id, m.chat_id, m.role, m.created_at,
+                snippet(messages_fts, 0, '<mark>', '</mark>', '…', 8) as snippet,
+                bm25(messages_fts) as rank
+         FROM messages_fts
+         JOIN messages m ON m.rowid = messages_fts.rowid
+         WHERE messages_fts MATCH ?1
+         UNION ALL
+         SELECT NULL, c.id, 'chat', c.created_at,
+                snippet(chats_fts, 0, '<mark>', '</mark>', '…', 8) as snippet,
+                bm25(chats_fts) as rank
+         FROM chats_fts
+         JOIN chats c ON c.rowid = chats_fts.rowid
+         WHERE chats_fts MATCH ?1
+         ORDER BY rank
+         LIMIT ?2",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let results = stmt
+        .query_map(params![query, limit], |row| {
+            Ok(MessageSearchResult {
+                message_id: row.get(0)?,
+                chat_id: row.get(1)?,
+                role: row.get(2)?,
+                created_at: row.get(3)?,
+                snippet: row.get(4)?,
+                rank: row.get(5)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    println!("✅ search_messages returned {} results", results.len());
+    Ok(results)
+}
+
 #[tauri::command]
 pub async fn update_chat_title(
     chat_id: String,
     title: String,
-    db: State<'_, DbConnection>,
+    pool: State<'_, DbPool>,
 ) -> Result<(), String> {
-    let conn =
-        db.0.lock()
-            .map_err(|_| "Failed to lock database connection")?;
+    let conn = pool.get().map_err(|e| e.to_string())?;
     conn.execute(
         "UPDATE chats SET title = ?1 WHERE id = ?2",
         [&title, &chat_id],
@@ -0,0 +1,341 @@
+use crate::mcp::McpServer;
+use crate::DbPool;
+use chrono::Utc;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use ts_rs::TS;
+use uuid::Uuid;
+
+/// How often the worker checks for newly enqueued jobs.
+const JOB_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Results larger than this are written to the artifacts directory instead of the row.
+const ARTIFACT_THRESHOLD_BYTES: usize = 32 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobState {
+    fn as_str(&self) -> &'static str {
+        match self {
+            JobState::Pending => "pending",
+            JobState::Running => "running",
+            JobState::Completed => "completed",
+            JobState::Failed => "failed",
+            JobState::Cancelled => "cancelled",
+        }
+    }
+
+    fn parse(s: &str) -> Self {
+        match s {
+            "running" => JobState::Running,
+            "completed" => JobState::Completed,
+            "failed" => JobState::Failed,
+            "cancelled" => JobState::Cancelled,
+            _ => JobState::Pending,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Job {
+    pub id: String,
+    pub chat_id: Option<String>,
+    pub server_id: String,
+    pub tool_name: String,
+    pub params: serde_json::Value,
+    pub state: JobState,
+    pub created_at: String,
+    pub finished_at: Option<String>,
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+    pub artifact_path: Option<String>,
+}
+
+fn row_to_job(row: &rusqlite::Row) -> rusqlite::Result<Job> {
+    let params_json: String = row.get(4)?;
+    let state: String = row.get(5)?;
+    let result_json: Option<String> = row.get(8)?;
+
+    Ok(Job {
+        id: row.get(0)?,
+        chat_id: row.get(1)?,
+        server_id: row.get(2)?,
+        tool_name: row.get(3)?,
+        params: serde_json::from_str(&params_json).unwrap_or(serde_json::Value::Null),
+        state: JobState::parse(&state),
+        created_at: row.get(6)?,
+        finished_at: row.get(7)?,
+        result: result_json.and_then(|s| serde_json::from_str(&s).ok()),
+        error: row.get(9)?,
+        artifact_path: row.get(10)?,
+    })
+}
+
+const JOB_COLUMNS: &str =
+    "id, chat_id, server_id, tool_name, params, state, created_at, finished_at, result, error, artifact_path";
+
+#[tauri::command]
+pub async fn enqueue_tool_job(
+    pool: State<'_, DbPool>,
+    chat_id: Option<String>,
+    server_id: String,
+    tool_name: String,
+    params: serde_json::Value,
+) -> Result<Job, String> {
+    let conn = pool.get().map_err(|e| e.to_string())?;
+
+    let job = Job {
+        id: Uuid::new_v4().to_string(),
+        chat_id,
+        server_id,
+        tool_name,
+        params,
+        state: JobState::Pending,
+        created_at: Utc::now().to_rfc3339(),
+        finished_at: None,
+        result: None,
+        error: None,
+        artifact_path: None,
+    };
+
+    conn.execute(
+        "INSERT INTO jobs (id, chat_id, server_id, tool_name, params, state, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            &job.id,
+            &job.chat_id,
+            &job.server_id,
+            &job.tool_name,
+            serde_json::to_string(&job.params).map_err(|e| e.to_string())?,
+            job.state.as_str(),
+            &job.created_at,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    println!("📥 enqueue_tool_job queued {} ({})", job.id, job.tool_name);
+    Ok(job)
+}
+
+#[tauri::command]
+pub async fn get_jobs(pool: State<'_, DbPool>, chat_id: Option<String>) -> Result<Vec<Job>, String> {
+    let conn = pool.get().map_err(|e| e.to_string())?;
+
+    // `IS` rather than `=` so a `None` chat_id (a standalone tool call not tied to any
+    // conversation, per enqueue_tool_job) matches the NULL rows instead of matching nothing.
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM jobs WHERE chat_id IS ?1 ORDER BY created_at DESC",
+            JOB_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+
+    stmt.query_map(params![chat_id], row_to_job)
+        .map_err(|e| e.to_string())?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn cancel_job(pool: State<'_, DbPool>, job_id: String) -> Result<(), String> {
+    let conn = pool.get().map_err(|e| e.to_string())?;
+
+    let updated = conn
+        .execute(
+            "UPDATE jobs SET state = ?1, finished_at = ?2 WHERE id = ?3 AND state = ?4",
+            params![
+                JobState::Cancelled.as_str(),
+                Utc::now().to_rfc3339(),
+                job_id,
+                JobState::Pending.as_str(),
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+    if updated == 0 {
+        return Err("Job is not pending (already running or finished)".to_string());
+    }
+
+    Ok(())
+}
+
+/// Claims the oldest pending job, if any, by atomically flipping it to `Running` --
+/// the `WHERE state = 'pending'` guard means a job cancelled between polls is never
+/// claimed by the worker.
+fn claim_next_job(pool: &DbPool, app: &AppHandle) -> Result<Option<Job>, String> {
+    let conn = pool.get().map_err(|e| e.to_string())?;
+
+    let job = conn
+        .query_row(
+            &format!(
+                "SELECT {} FROM jobs WHERE state = 'pending' ORDER BY created_at ASC LIMIT 1",
+                JOB_COLUMNS
+            ),
+            [],
+            row_to_job,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    let Some(job) = job else { return Ok(None) };
+
+    let claimed = conn
+        .execute(
+            "UPDATE jobs SET state = 'running' WHERE id = ?1 AND state = 'pending'",
+            [&job.id],
+        )
+        .map_err(|e| e.to_string())?;
+
+    if claimed == 0 {
+        return Ok(None);
+    }
+
+    let _ = app.emit(&format!("jobs://state/{}", job.id), JobState::Running);
+    Ok(Some(job))
+}
+
+fn finish_job(
+    pool: &DbPool,
+    app: &AppHandle,
+    job_id: &str,
+    state: JobState,
+    result: Option<serde_json::Value>,
+    error: Option<String>,
+    artifact_path: Option<String>,
+) -> Result<(), String> {
+    let conn = pool.get().map_err(|e| e.to_string())?;
+
+    conn.execute(
+        "UPDATE jobs SET state = ?1, finished_at = ?2, result = ?3, error = ?4, artifact_path = ?5 WHERE id = ?6",
+        params![
+            state.as_str(),
+            Utc::now().to_rfc3339(),
+            result.map(|r| serde_json::to_string(&r)).transpose().map_err(|e| e.to_string())?,
+            error,
+            artifact_path,
+            job_id,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let _ = app.emit(&format!("jobs://state/{}", job_id), state);
+    Ok(())
+}
+
+/// Runs one job: dispatches its `tools/call` to the named MCP server, stores the result
+/// (spilling large payloads to the artifacts directory), and transitions it to its
+/// terminal state.
+async fn run_job(
+    job: Job,
+    pool: DbPool,
+    servers: Arc<Mutex<HashMap<String, Arc<McpServer>>>>,
+    app: AppHandle,
+    artifacts_dir: std::path::PathBuf,
+) {
+    let server = servers.lock().ok().and_then(|s| s.get(&job.server_id).cloned());
+
+    let Some(server) = server else {
+        let _ = finish_job(
+            &pool,
+            &app,
+            &job.id,
+            JobState::Failed,
+            None,
+            Some(format!("MCP server '{}' not found", job.server_id)),
+            None,
+        );
+        return;
+    };
+
+    let call_result = server
+        .send_command(
+            "tools/call",
+            Some(serde_json::json!({
+                "name": job.tool_name,
+                "arguments": job.params,
+            })),
+        )
+        .await;
+
+    match call_result {
+        Ok(value) => {
+            let serialized = serde_json::to_string(&value).unwrap_or_default();
+            if serialized.len() > ARTIFACT_THRESHOLD_BYTES {
+                let artifact_path = artifacts_dir.join(format!("{}.json", job.id));
+                match std::fs::write(&artifact_path, &serialized) {
+                    Ok(()) => {
+                        let _ = finish_job(
+                            &pool,
+                            &app,
+                            &job.id,
+                            JobState::Completed,
+                            None,
+                            None,
+                            Some(artifact_path.to_string_lossy().to_string()),
+                        );
+                    }
+                    Err(e) => {
+                        let _ = finish_job(
+                            &pool,
+                            &app,
+                            &job.id,
+                            JobState::Failed,
+                            None,
+                            Some(format!("Failed to write artifact: {}", e)),
+                            None,
+                        );
+                    }
+                }
+            } else {
+                let _ = finish_job(&pool, &app, &job.id, JobState::Completed, Some(value), None, None);
+            }
+        }
+        Err(e) => {
+            let _ = finish_job(&pool, &app, &job.id, JobState::Failed, None, Some(e), None);
+        }
+    }
+}
+
+/// Background worker: polls for `Pending` jobs and runs them one at a time, modeled on a
+/// CI driver's build queue. Started once from `run()`.
+pub fn start_job_worker(
+    pool: DbPool,
+    servers: Arc<Mutex<HashMap<String, Arc<McpServer>>>>,
+    app: AppHandle,
+) {
+    let artifacts_dir = app
+        .path()
+        .app_data_dir()
+        .expect("Failed to get app data directory")
+        .join("artifacts");
+    std::fs::create_dir_all(&artifacts_dir).expect("Failed to create artifacts directory");
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match claim_next_job(&pool, &app) {
+                Ok(Some(job)) => {
+                    run_job(job, pool.clone(), servers.clone(), app.clone(), artifacts_dir.clone()).await;
+                }
+                Ok(None) => tokio::time::sleep(JOB_POLL_INTERVAL).await,
+                Err(e) => {
+                    eprintln!("Job worker failed to poll for pending jobs: {}", e);
+                    tokio::time::sleep(JOB_POLL_INTERVAL).await;
+                }
+            }
+        }
+    });
+}
@@ -1,14 +1,16 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 use chrono::{DateTime, Utc};
-use rusqlite::{Connection, Result};
+use r2d2_sqlite::SqliteConnectionManager;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Mutex;
 use tauri::{path::PathResolver, Manager, State};
 use ts_rs::TS;
 use uuid::Uuid;
 
 mod commands;
+mod jobs;
+mod mcp;
+mod migrations;
 
 #[derive(Debug, Serialize, Deserialize, TS)]
 #[ts(export)]
@@ -39,9 +41,27 @@ pub struct Message {
     pub attachments: Option<Vec<FileAttachment>>,
 }
 
-pub struct DbConnection(Mutex<Connection>);
+#[derive(Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct MessageSearchResult {
+    /// `None` when the match came from a chat's title rather than a specific message.
+    pub message_id: Option<String>,
+    pub chat_id: String,
+    pub role: String,
+    pub created_at: String,
+    /// The matched content (message body, or chat title for a title match) with `<mark>`
+    /// tags around matched terms.
+    pub snippet: String,
+    /// BM25 relevance score from FTS5; lower is more relevant.
+    pub rank: f64,
+}
 
-pub fn init_db(app: &tauri::App) -> Result<Connection> {
+/// Pooled connection manager for the chats database. WAL mode lets readers proceed
+/// concurrently, so commands check out a pooled connection instead of sharing one
+/// behind a single global lock.
+pub type DbPool = r2d2::Pool<SqliteConnectionManager>;
+
+pub fn init_db(app: &tauri::App) -> Result<DbPool, String> {
     println!("🗄️ Initializing database...");
 
     let app_dir = app
@@ -54,66 +74,26 @@ pub fn init_db(app: &tauri::App) -> Result<Connection> {
     let db_path = app_dir.join("chats.db");
     println!("📁 Database path: {:?}", db_path);
 
-    let conn = Connection::open(db_path)?;
-
-    // Enable foreign keys and WAL mode
-    conn.pragma_update(None, "foreign_keys", "ON")?;
-    conn.pragma_update(None, "journal_mode", "WAL")?;
-
-    println!("📊 Creating tables...");
-
-    // Create chats table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS chats (
-            id TEXT PRIMARY KEY,
-            title TEXT NOT NULL,
-            created_at TEXT NOT NULL,
-            updated_at TEXT NOT NULL
-        )",
-        [],
-    )?;
-
-    // Create messages table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS messages (
-            id TEXT PRIMARY KEY,
-            chat_id TEXT NOT NULL,
-            content TEXT NOT NULL,
-            role TEXT NOT NULL CHECK(role IN ('user', 'assistant', 'system')),
-            created_at TEXT NOT NULL,
-            FOREIGN KEY(chat_id) REFERENCES chats(id) ON DELETE CASCADE
-        )",
-        [],
-    )?;
-
-    // Create attachments table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS attachments (
-            id TEXT PRIMARY KEY,
-            message_id TEXT NOT NULL,
-            name TEXT NOT NULL,
-            content TEXT NOT NULL,
-            type TEXT NOT NULL,
-            size INTEGER NOT NULL,
-            FOREIGN KEY(message_id) REFERENCES messages(id) ON DELETE CASCADE
-        )",
-        [],
-    )?;
-
-    // Create api_keys table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS api_keys (
-            key_type TEXT PRIMARY KEY,
-            key_value TEXT NOT NULL
-        )",
-        [],
-    )?;
+    // Enable foreign keys and WAL mode on every pooled connection as it's created.
+    let manager = SqliteConnectionManager::file(db_path).with_init(|conn| {
+        conn.pragma_update(None, "foreign_keys", "ON")?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        Ok(())
+    });
+    let pool = r2d2::Pool::new(manager).map_err(|e| e.to_string())?;
+
+    println!("📊 Running migrations...");
+    let mut conn = pool.get().map_err(|e| e.to_string())?;
+    migrations::run_migrations(&mut conn).map_err(|e| e.to_string())?;
 
     // Print table schemas for debugging
     let schemas: Vec<String> = conn
-        .prepare("SELECT sql FROM sqlite_master WHERE type='table'")?
-        .query_map([], |row| row.get(0))?
-        .collect::<Result<Vec<_>, _>>()?;
+        .prepare("SELECT sql FROM sqlite_master WHERE type='table'")
+        .map_err(|e| e.to_string())?
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| e.to_string())?;
 
     println!("📋 Table schemas:");
     for schema in schemas {
@@ -121,15 +101,27 @@ pub fn init_db(app: &tauri::App) -> Result<Connection> {
     }
 
     println!("✅ Database initialization complete");
-    Ok(conn)
+    Ok(pool)
 }
 
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_fs::init())
         .setup(|app| {
-            let conn = init_db(app).expect("Database initialization failed");
-            app.manage(DbConnection(Mutex::new(conn)));
+            let pool = init_db(app).expect("Database initialization failed");
+
+            let mcp_state = mcp::McpState::default();
+            mcp::start_supervisor(
+                mcp_state.servers.clone(),
+                mcp_state.restart_attempts.clone(),
+                app.handle().clone(),
+            );
+
+            jobs::start_job_worker(pool.clone(), mcp_state.servers.clone(), app.handle().clone());
+
+            app.manage(pool);
+            app.manage(mcp_state);
+
             Ok(())
         })
         .plugin(tauri_plugin_opener::init())
@@ -141,6 +133,17 @@ pub fn run() {
             commands::save_api_key,
             commands::get_api_keys,
             commands::update_chat_title,
+            commands::search_messages,
+            mcp::start_mcp_server,
+            mcp::stop_mcp_server,
+            mcp::restart_mcp_server,
+            mcp::get_mcp_server_status,
+            mcp::send_mcp_command,
+            mcp::list_mcp_tools,
+            mcp::call_mcp_tool,
+            jobs::enqueue_tool_job,
+            jobs::get_jobs,
+            jobs::cancel_job,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
@@ -3,14 +3,70 @@ use serde_json;
 use std::collections::HashMap;
 use std::io::{BufRead, BufReader, Write};
 use std::process::{Child, Command, Stdio};
-use std::sync::{mpsc, Arc, Mutex};
-use tauri::{State, Emitter};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::oneshot;
+use ts_rs::TS;
+
+/// How long a single JSON-RPC request is allowed to wait for a matching response.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// MCP protocol version spoken during the `initialize` handshake.
+const MCP_PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// How often the supervisor thread polls server processes for unexpected exit.
+const SUPERVISOR_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Base delay for the first auto-restart attempt; doubles on each subsequent attempt.
+const RESTART_BACKOFF_BASE: Duration = Duration::from_secs(1);
+
+/// Ceiling on the exponential restart backoff.
+const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+/// How long a `Ready` server can have requests outstanding with no resolved response
+/// before the supervisor flags it `Unresponsive`.
+const UNRESPONSIVE_THRESHOLD: Duration = Duration::from_secs(10);
+
+/// Lifecycle state of a spawned MCP server, mirroring an agent-state model where each
+/// subprocess has an observable lifecycle instead of being fire-and-forget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum McpServerState {
+    Starting,
+    Initializing,
+    Ready,
+    Unresponsive,
+    Crashed,
+    Stopped,
+}
+
+/// Everything needed to spawn (or re-spawn) a server, kept around so the supervisor and
+/// `restart_mcp_server` can bring a server back up without the caller re-supplying it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ServerSpawnConfig {
+    command: String,
+    env_vars: Vec<EnvVar>,
+    persistent: bool,
+}
 
 #[derive(Debug)]
 pub struct McpServer {
-    process: Child,
-    stdin: std::process::ChildStdin,
-    stdout_rx: mpsc::Receiver<String>,
+    process: Mutex<Child>,
+    stdin: Mutex<std::process::ChildStdin>,
+    next_id: AtomicI64,
+    pending: Mutex<HashMap<i64, oneshot::Sender<serde_json::Value>>>,
+    /// Server capabilities negotiated during `initialize`.
+    capabilities: Mutex<Option<serde_json::Value>>,
+    /// Cached result of `tools/list`, so the frontend doesn't have to re-query it.
+    tools: Mutex<Option<serde_json::Value>>,
+    state: Mutex<McpServerState>,
+    spawn_config: ServerSpawnConfig,
+    /// Last time a response (or a fresh spawn, or a request sent while nothing was
+    /// outstanding) was observed; used to detect a server that's alive but no longer
+    /// answering requests.
+    last_activity: Mutex<Instant>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -24,53 +80,219 @@ struct JsonRpcMessage {
 }
 
 impl McpServer {
-    fn new(
-        process: Child,
-        stdin: std::process::ChildStdin,
-        stdout_rx: mpsc::Receiver<String>,
-    ) -> Self {
+    fn new(process: Child, stdin: std::process::ChildStdin, spawn_config: ServerSpawnConfig) -> Self {
         Self {
-            process,
-            stdin,
-            stdout_rx,
+            process: Mutex::new(process),
+            stdin: Mutex::new(stdin),
+            next_id: AtomicI64::new(1),
+            pending: Mutex::new(HashMap::new()),
+            capabilities: Mutex::new(None),
+            tools: Mutex::new(None),
+            state: Mutex::new(McpServerState::Starting),
+            spawn_config,
+            last_activity: Mutex::new(Instant::now()),
         }
     }
 
-    fn send_command(&mut self, command: &str) -> Result<String, String> {
-        // Send the command
-        self.stdin
-            .write_all(format!("{}\n", command).as_bytes())
-            .map_err(|e| format!("Failed to send command: {}", e))?;
-        self.stdin
+    fn state(&self) -> McpServerState {
+        *self.state.lock().expect("server state lock poisoned")
+    }
+
+    /// Whether there's at least one request awaiting a response.
+    fn has_pending(&self) -> bool {
+        !self.pending.lock().expect("pending requests lock poisoned").is_empty()
+    }
+
+    /// How long it's been since the last response, handshake completion, or request sent
+    /// while nothing was outstanding.
+    fn idle_for(&self) -> Duration {
+        self.last_activity
+            .lock()
+            .expect("last activity lock poisoned")
+            .elapsed()
+    }
+
+    /// Updates the server's lifecycle state and notifies the frontend.
+    fn set_state(&self, new_state: McpServerState, server_id: &str, app: &AppHandle) {
+        *self.state.lock().expect("server state lock poisoned") = new_state;
+        let _ = app.emit(&format!("mcp://state/{}", server_id), new_state);
+    }
+
+    /// Runs the MCP lifecycle handshake: `initialize`, then `notifications/initialized`,
+    /// then an eager `tools/list` so the frontend can discover tools without a round trip.
+    async fn initialize(&self, server_id: &str, app: &AppHandle) -> Result<(), String> {
+        self.set_state(McpServerState::Initializing, server_id, app);
+
+        let result = self
+            .send_command(
+                "initialize",
+                Some(serde_json::json!({
+                    "protocolVersion": MCP_PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {
+                        "name": "agentia",
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                })),
+            )
+            .await
+            .map_err(|e| {
+                self.set_state(McpServerState::Crashed, server_id, app);
+                e
+            })?;
+
+        *self
+            .capabilities
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))? = result.get("capabilities").cloned();
+
+        self.send_notification("notifications/initialized", None)
+            .map_err(|e| {
+                self.set_state(McpServerState::Crashed, server_id, app);
+                e
+            })?;
+
+        let tools = self
+            .send_command("tools/list", None)
+            .await
+            .map_err(|e| {
+                self.set_state(McpServerState::Crashed, server_id, app);
+                e
+            })?;
+        *self.tools.lock().map_err(|e| format!("Lock error: {}", e))? =
+            Some(tools.get("tools").cloned().unwrap_or(tools));
+
+        *self.last_activity.lock().map_err(|e| format!("Lock error: {}", e))? = Instant::now();
+        self.set_state(McpServerState::Ready, server_id, app);
+        Ok(())
+    }
+
+    /// Sends a JSON-RPC 2.0 notification (no `id`, no response expected).
+    fn send_notification(&self, method: &str, params: Option<serde_json::Value>) -> Result<(), String> {
+        let message = JsonRpcMessage {
+            id: None,
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+        };
+        let line = serde_json::to_string(&message).map_err(|e| e.to_string())?;
+
+        let mut stdin = self.stdin.lock().map_err(|e| format!("Lock error: {}", e))?;
+        stdin
+            .write_all(format!("{}\n", line).as_bytes())
+            .map_err(|e| format!("Failed to send notification: {}", e))?;
+        stdin
             .flush()
-            .map_err(|e| format!("Failed to flush stdin: {}", e))?;
+            .map_err(|e| format!("Failed to flush stdin: {}", e))
+    }
+
+    /// Sends a JSON-RPC 2.0 request and awaits the response matching its id. Returns the
+    /// response's `result` member; an `error` response is returned as `Err`.
+    pub(crate) async fn send_command(
+        &self,
+        method: &str,
+        params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+
+        let (tx, rx) = oneshot::channel();
+        {
+            let mut pending = self.pending.lock().map_err(|e| format!("Lock error: {}", e))?;
+            // A request to a server with nothing outstanding starts the unresponsiveness
+            // clock now, rather than from whenever the last response happened to arrive.
+            if pending.is_empty() {
+                if let Ok(mut last_activity) = self.last_activity.lock() {
+                    *last_activity = Instant::now();
+                }
+            }
+            pending.insert(id, tx);
+        }
+
+        let message = JsonRpcMessage {
+            id: Some(serde_json::json!(id)),
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+        };
+        let line = serde_json::to_string(&message).map_err(|e| e.to_string())?;
+
+        {
+            let mut stdin = self.stdin.lock().map_err(|e| format!("Lock error: {}", e))?;
+            stdin
+                .write_all(format!("{}\n", line).as_bytes())
+                .map_err(|e| format!("Failed to send command: {}", e))?;
+            stdin
+                .flush()
+                .map_err(|e| format!("Failed to flush stdin: {}", e))?;
+        }
 
-        Ok("".to_string())
+        match tokio::time::timeout(REQUEST_TIMEOUT, rx).await {
+            Ok(Ok(response)) => match response.get("error") {
+                Some(error) => Err(format!("Server returned an error: {}", error)),
+                None => Ok(response.get("result").cloned().unwrap_or(serde_json::Value::Null)),
+            },
+            Ok(Err(_)) => Err("Server closed before sending a response".to_string()),
+            Err(_) => {
+                self.pending
+                    .lock()
+                    .map_err(|e| format!("Lock error: {}", e))?
+                    .remove(&id);
+                Err(format!("Request {} timed out waiting for a response", id))
+            }
+        }
+    }
+
+    /// Resolves the pending request for `id`, if any, with the given response value.
+    /// Any response is evidence of life, so this also clears a stale `Unresponsive` flag.
+    fn resolve(&self, id: i64, value: serde_json::Value, server_id: &str, app: &AppHandle) {
+        if let Ok(mut last_activity) = self.last_activity.lock() {
+            *last_activity = Instant::now();
+        }
+        if self.state() == McpServerState::Unresponsive {
+            self.set_state(McpServerState::Ready, server_id, app);
+        }
+
+        if let Some(tx) = self
+            .pending
+            .lock()
+            .expect("pending requests lock poisoned")
+            .remove(&id)
+        {
+            let _ = tx.send(value);
+        }
     }
 }
 
-pub struct McpState(pub Arc<Mutex<HashMap<String, McpServer>>>);
+pub struct McpState {
+    pub servers: Arc<Mutex<HashMap<String, Arc<McpServer>>>>,
+    /// Consecutive auto-restart attempts per server id, tracked independently of any
+    /// single `McpServer` instance (a restart creates a brand-new instance, so this
+    /// can't live on the struct itself without losing the count on every restart).
+    pub(crate) restart_attempts: Arc<Mutex<HashMap<String, u32>>>,
+}
 
-#[derive(Debug, Serialize, Deserialize)]
+impl Default for McpState {
+    fn default() -> Self {
+        Self {
+            servers: Arc::new(Mutex::new(HashMap::new())),
+            restart_attempts: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnvVar {
     key: String,
     value: String,
 }
 
-#[tauri::command]
-pub async fn start_mcp_server(
+/// Spawns the shell subprocess for a server and wires up its stdout/stderr reader threads.
+/// Shared between the initial `start_mcp_server` command and supervisor-driven restarts.
+fn spawn_server(
     server_id: String,
-    command: String,
-    env_vars: Vec<EnvVar>,
-    state: State<'_, McpState>,
-    app: tauri::AppHandle,
-) -> Result<String, String> {
-    let mut servers = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
-
-    if servers.contains_key(&server_id) {
-        return Err("Server with this ID already exists".to_string());
-    }
-
+    config: ServerSpawnConfig,
+    app: AppHandle,
+) -> Result<Arc<McpServer>, String> {
     let shell = if cfg!(target_os = "windows") {
         "cmd"
     } else {
@@ -84,14 +306,13 @@ pub async fn start_mcp_server(
 
     let mut cmd = Command::new(shell);
     cmd.arg(shell_arg)
-        .arg(&command)
+        .arg(&config.command)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
 
-    // Add environment variables
-    for env_var in env_vars {
-        cmd.env(env_var.key, env_var.value);
+    for env_var in &config.env_vars {
+        cmd.env(&env_var.key, &env_var.value);
     }
 
     let mut child = cmd
@@ -103,31 +324,49 @@ pub async fn start_mcp_server(
         .take()
         .ok_or_else(|| "Failed to capture stdin".to_string())?;
 
-    // Set up stdout channel
-    let (stdout_tx, stdout_rx) = mpsc::channel();
+    let server = Arc::new(McpServer::new(child, stdin, config));
+
+    // Set up stdout/stderr reading in separate threads. Each stdout line is parsed as a
+    // JSON-RPC message: responses (carrying an `id`) resolve the matching pending request,
+    // while server-initiated notifications (no `id`) are forwarded to the frontend.
+    let (stdout, stderr) = {
+        let mut process = server.process.lock().map_err(|e| format!("Lock error: {}", e))?;
+        (process.stdout.take(), process.stderr.take())
+    };
 
-    // Set up stdout reading in a separate thread
-    if let Some(stdout) = child.stdout.take() {
+    if let Some(stdout) = stdout {
         let reader = BufReader::new(stdout);
-        let tx = stdout_tx.clone();
+        let server_clone = server.clone();
         let server_id_clone = server_id.clone();
         let app_handle = app.clone();
         std::thread::spawn(move || {
             for line in reader.lines() {
-                if let Ok(line) = line {
-                    // Send to channel for command responses
-                    if tx.send(line.clone()).is_err() {
-                        break;
+                let Ok(line) = line else { break };
+
+                let parsed: Result<serde_json::Value, _> = serde_json::from_str(&line);
+                match parsed {
+                    Ok(value) => {
+                        let id = value.get("id").and_then(|id| id.as_i64());
+                        let is_response =
+                            value.get("result").is_some() || value.get("error").is_some();
+
+                        if let (Some(id), true) = (id, is_response) {
+                            server_clone.resolve(id, value, &server_id_clone, &app_handle);
+                        } else {
+                            // Server-initiated notification (no id): forward to the frontend.
+                            let _ = app_handle
+                                .emit(&format!("mcp://stdout/{}", server_id_clone), value);
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to parse MCP message from {}: {}", server_id_clone, e);
                     }
-                    // Emit event to frontend
-                    let _ = app_handle.emit(&format!("mcp://stdout/{}", server_id_clone), line);
                 }
             }
         });
     }
 
-    // Set up stderr reading in a separate thread
-    if let Some(stderr) = child.stderr.take() {
+    if let Some(stderr) = stderr {
         let reader = BufReader::new(stderr);
         let server_id_clone = server_id.clone();
         let app_handle = app.clone();
@@ -142,25 +381,286 @@ pub async fn start_mcp_server(
         });
     }
 
-    let server = McpServer::new(child, stdin, stdout_rx);
-    servers.insert(server_id.clone(), server);
+    Ok(server)
+}
 
-    Ok(format!("Process {} started successfully", server_id))
+/// Spawns a server, runs its handshake, and inserts it into `servers` under `server_id`.
+/// Used both for the user-initiated `start_mcp_server` command and for restarts.
+async fn start_server(
+    server_id: String,
+    config: ServerSpawnConfig,
+    app: AppHandle,
+    servers: Arc<Mutex<HashMap<String, Arc<McpServer>>>>,
+    restart_attempts: Arc<Mutex<HashMap<String, u32>>>,
+) -> Result<(), String> {
+    let server = spawn_server(server_id.clone(), config, app.clone())?;
+    servers
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .insert(server_id.clone(), server.clone());
+
+    // Perform the MCP lifecycle handshake after the server is already visible in the map,
+    // so get_mcp_server_status can observe Starting/Initializing rather than nothing at all.
+    let result = server.initialize(&server_id, &app).await;
+
+    // A clean handshake means this server id is healthy again; forget any prior backoff.
+    if result.is_ok() {
+        if let Ok(mut attempts) = restart_attempts.lock() {
+            attempts.remove(&server_id);
+        }
+    }
+
+    result
 }
 
 #[tauri::command]
-pub async fn send_mcp_command(
+pub async fn start_mcp_server(
     server_id: String,
     command: String,
+    env_vars: Vec<EnvVar>,
+    persistent: Option<bool>,
     state: State<'_, McpState>,
+    app: tauri::AppHandle,
 ) -> Result<String, String> {
-    // log
-    println!("sending command: {}", command);
-    let mut servers = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    {
+        let servers = state.servers.lock().map_err(|e| format!("Lock error: {}", e))?;
+        if servers.contains_key(&server_id) {
+            return Err("Server with this ID already exists".to_string());
+        }
+    }
+
+    let config = ServerSpawnConfig {
+        command,
+        env_vars,
+        persistent: persistent.unwrap_or(false),
+    };
 
-    let server = servers
-        .get_mut(&server_id)
+    start_server(
+        server_id.clone(),
+        config,
+        app,
+        state.servers.clone(),
+        state.restart_attempts.clone(),
+    )
+    .await?;
+
+    Ok(format!("Process {} started successfully", server_id))
+}
+
+#[tauri::command]
+pub async fn stop_mcp_server(server_id: String, state: State<'_, McpState>) -> Result<(), String> {
+    let server = state
+        .servers
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .remove(&server_id)
         .ok_or_else(|| "Server not found".to_string())?;
 
-    server.send_command(&command)
+    *server.state.lock().map_err(|e| format!("Lock error: {}", e))? = McpServerState::Stopped;
+
+    let mut process = server.process.lock().map_err(|e| format!("Lock error: {}", e))?;
+    process
+        .kill()
+        .map_err(|e| format!("Failed to kill process: {}", e))?;
+    let _ = process.wait();
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn restart_mcp_server(
+    server_id: String,
+    state: State<'_, McpState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let config = {
+        let mut servers = state.servers.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let server = servers
+            .remove(&server_id)
+            .ok_or_else(|| "Server not found".to_string())?;
+        if let Ok(mut process) = server.process.lock() {
+            let _ = process.kill();
+            let _ = process.wait();
+        }
+        server.spawn_config.clone()
+    };
+
+    start_server(
+        server_id,
+        config,
+        app,
+        state.servers.clone(),
+        state.restart_attempts.clone(),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn get_mcp_server_status(
+    state: State<'_, McpState>,
+) -> Result<HashMap<String, McpServerState>, String> {
+    let servers = state.servers.lock().map_err(|e| format!("Lock error: {}", e))?;
+    Ok(servers.iter().map(|(id, server)| (id.clone(), server.state())).collect())
+}
+
+/// Background supervisor: periodically checks every server's process for unexpected exit
+/// (and for a `Ready` server that's stopped answering requests), flags it accordingly, and
+/// if configured as persistent, restarts it with exponential backoff that escalates across
+/// the server id's repeated crashes. Started once from `run()`.
+pub fn start_supervisor(
+    servers: Arc<Mutex<HashMap<String, Arc<McpServer>>>>,
+    restart_attempts: Arc<Mutex<HashMap<String, u32>>>,
+    app: AppHandle,
+) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(SUPERVISOR_POLL_INTERVAL);
+
+        let snapshot: Vec<(String, Arc<McpServer>)> = match servers.lock() {
+            Ok(guard) => guard.iter().map(|(id, s)| (id.clone(), s.clone())).collect(),
+            Err(_) => continue,
+        };
+
+        for (server_id, server) in snapshot {
+            if server.state() == McpServerState::Stopped {
+                continue;
+            }
+
+            let exited = matches!(
+                server.process.lock().map(|mut p| p.try_wait()),
+                Ok(Ok(Some(_)))
+            );
+
+            if !exited {
+                if server.state() == McpServerState::Ready
+                    && server.has_pending()
+                    && server.idle_for() > UNRESPONSIVE_THRESHOLD
+                {
+                    server.set_state(McpServerState::Unresponsive, &server_id, &app);
+                }
+                continue;
+            }
+
+            // The server process exited. Only mark it crashed and remove it from the map
+            // if our snapshot is still the current occupant of `server_id` -- a concurrent
+            // restart_mcp_server call (or another supervisor cycle) may have already
+            // replaced it with a fresh, healthy instance, and that one must be left alone.
+            let still_current = match servers.lock() {
+                Ok(guard) => matches!(guard.get(&server_id), Some(current) if Arc::ptr_eq(current, &server)),
+                Err(_) => false,
+            };
+            if !still_current {
+                continue;
+            }
+
+            server.set_state(McpServerState::Crashed, &server_id, &app);
+            servers.lock().ok().map(|mut guard| {
+                if matches!(guard.get(&server_id), Some(current) if Arc::ptr_eq(current, &server)) {
+                    guard.remove(&server_id);
+                }
+            });
+
+            if !server.spawn_config.persistent {
+                continue;
+            }
+
+            let attempt = restart_attempts
+                .lock()
+                .map(|mut attempts| {
+                    let count = attempts.entry(server_id.clone()).or_insert(0);
+                    let current = *count;
+                    *count += 1;
+                    current
+                })
+                .unwrap_or(0);
+            let backoff = RESTART_BACKOFF_BASE
+                .saturating_mul(1u32.checked_shl(attempt).unwrap_or(u32::MAX))
+                .min(RESTART_BACKOFF_MAX);
+
+            let servers_clone = servers.clone();
+            let restart_attempts_clone = restart_attempts.clone();
+            let app_clone = app.clone();
+            let config = server.spawn_config.clone();
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(backoff).await;
+                if let Err(e) = start_server(
+                    server_id.clone(),
+                    config,
+                    app_clone,
+                    servers_clone,
+                    restart_attempts_clone,
+                )
+                .await
+                {
+                    eprintln!("Failed to auto-restart MCP server {}: {}", server_id, e);
+                }
+            });
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn list_mcp_tools(
+    server_id: String,
+    state: State<'_, McpState>,
+) -> Result<serde_json::Value, String> {
+    let server = {
+        let servers = state.servers.lock().map_err(|e| format!("Lock error: {}", e))?;
+        servers
+            .get(&server_id)
+            .cloned()
+            .ok_or_else(|| "Server not found".to_string())?
+    };
+
+    server
+        .tools
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .clone()
+        .ok_or_else(|| "Server has not completed initialization yet".to_string())
+}
+
+#[tauri::command]
+pub async fn call_mcp_tool(
+    server_id: String,
+    tool_name: String,
+    arguments: Option<serde_json::Value>,
+    state: State<'_, McpState>,
+) -> Result<serde_json::Value, String> {
+    let server = {
+        let servers = state.servers.lock().map_err(|e| format!("Lock error: {}", e))?;
+        servers
+            .get(&server_id)
+            .cloned()
+            .ok_or_else(|| "Server not found".to_string())?
+    };
+
+    server
+        .send_command(
+            "tools/call",
+            Some(serde_json::json!({
+                "name": tool_name,
+                "arguments": arguments.unwrap_or(serde_json::json!({})),
+            })),
+        )
+        .await
+}
+
+#[tauri::command]
+pub async fn send_mcp_command(
+    server_id: String,
+    method: String,
+    params: Option<serde_json::Value>,
+    state: State<'_, McpState>,
+) -> Result<serde_json::Value, String> {
+    println!("sending command: {} {:?}", method, params);
+
+    let server = {
+        let servers = state.servers.lock().map_err(|e| format!("Lock error: {}", e))?;
+        servers
+            .get(&server_id)
+            .cloned()
+            .ok_or_else(|| "Server not found".to_string())?
+    };
+
+    server.send_command(&method, params).await
 }
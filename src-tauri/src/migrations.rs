@@ -0,0 +1,141 @@
+use rusqlite::{Connection, Result, Transaction};
+
+/// A single schema change, identified by the `user_version` it brings the database to.
+/// Migrations are applied in order, each inside its own transaction, and each bumps
+/// `user_version` so re-running `run_migrations` against an up-to-date database is a no-op.
+struct Migration {
+    version: i64,
+    run: fn(&Transaction) -> Result<()>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        run: create_initial_schema,
+    },
+    Migration {
+        version: 2,
+        run: add_fts_search,
+    },
+    Migration {
+        version: 3,
+        run: add_jobs_table,
+    },
+];
+
+fn create_initial_schema(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS chats (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS messages (
+            id TEXT PRIMARY KEY,
+            chat_id TEXT NOT NULL,
+            content TEXT NOT NULL,
+            role TEXT NOT NULL CHECK(role IN ('user', 'assistant', 'system')),
+            created_at TEXT NOT NULL,
+            FOREIGN KEY(chat_id) REFERENCES chats(id) ON DELETE CASCADE
+        );
+        CREATE TABLE IF NOT EXISTS attachments (
+            id TEXT PRIMARY KEY,
+            message_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            content TEXT NOT NULL,
+            type TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            FOREIGN KEY(message_id) REFERENCES messages(id) ON DELETE CASCADE
+        );
+        CREATE TABLE IF NOT EXISTS api_keys (
+            key_type TEXT PRIMARY KEY,
+            key_value TEXT NOT NULL
+        );",
+    )
+}
+
+/// Adds FTS5 virtual tables mirroring `messages.content` and `chats.title`, kept in sync
+/// via triggers, so chat history becomes searchable instead of opaque. The content tables
+/// already have the rowids FTS5 needs (neither `messages` nor `chats` is `WITHOUT ROWID`),
+/// so we sync by rowid and backfill any rows that existed before this migration ran.
+fn add_fts_search(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+            content,
+            content='messages',
+            content_rowid='rowid'
+        );
+        CREATE TRIGGER IF NOT EXISTS messages_fts_ai AFTER INSERT ON messages BEGIN
+            INSERT INTO messages_fts(rowid, content) VALUES (new.rowid, new.content);
+        END;
+        CREATE TRIGGER IF NOT EXISTS messages_fts_ad AFTER DELETE ON messages BEGIN
+            INSERT INTO messages_fts(messages_fts, rowid, content) VALUES ('delete', old.rowid, old.content);
+        END;
+        CREATE TRIGGER IF NOT EXISTS messages_fts_au AFTER UPDATE ON messages BEGIN
+            INSERT INTO messages_fts(messages_fts, rowid, content) VALUES ('delete', old.rowid, old.content);
+            INSERT INTO messages_fts(rowid, content) VALUES (new.rowid, new.content);
+        END;
+        INSERT INTO messages_fts(rowid, content) SELECT rowid, content FROM messages;
+
+        CREATE VIRTUAL TABLE IF NOT EXISTS chats_fts USING fts5(
+            title,
+            content='chats',
+            content_rowid='rowid'
+        );
+        CREATE TRIGGER IF NOT EXISTS chats_fts_ai AFTER INSERT ON chats BEGIN
+            INSERT INTO chats_fts(rowid, title) VALUES (new.rowid, new.title);
+        END;
+        CREATE TRIGGER IF NOT EXISTS chats_fts_ad AFTER DELETE ON chats BEGIN
+            INSERT INTO chats_fts(chats_fts, rowid, title) VALUES ('delete', old.rowid, old.title);
+        END;
+        CREATE TRIGGER IF NOT EXISTS chats_fts_au AFTER UPDATE ON chats BEGIN
+            INSERT INTO chats_fts(chats_fts, rowid, title) VALUES ('delete', old.rowid, old.title);
+            INSERT INTO chats_fts(rowid, title) VALUES (new.rowid, new.title);
+        END;
+        INSERT INTO chats_fts(rowid, title) SELECT rowid, title FROM chats;",
+    )
+}
+
+/// Adds the `jobs` table backing the tool-call job queue: every tool call enqueued through
+/// `enqueue_tool_job` is recorded here and driven through `pending` -> `running` -> `completed`/`failed`/`cancelled`
+/// by the background worker, so long-running agent actions survive app restarts.
+fn add_jobs_table(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS jobs (
+            id TEXT PRIMARY KEY,
+            chat_id TEXT,
+            server_id TEXT NOT NULL,
+            tool_name TEXT NOT NULL,
+            params TEXT NOT NULL,
+            state TEXT NOT NULL CHECK(state IN ('pending', 'running', 'completed', 'failed', 'cancelled')),
+            created_at TEXT NOT NULL,
+            finished_at TEXT,
+            result TEXT,
+            error TEXT,
+            artifact_path TEXT,
+            FOREIGN KEY(chat_id) REFERENCES chats(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS jobs_chat_id_idx ON jobs(chat_id);
+        CREATE INDEX IF NOT EXISTS jobs_state_idx ON jobs(state);",
+    )
+}
+
+/// Steps `conn` from its current `user_version` up to the latest migration, each step
+/// applied in a transaction that's rolled back on failure. Adding a migration is the
+/// standard way to evolve the `chats`/`messages`/`attachments`/`api_keys` schema going
+/// forward: append a new `Migration` to `MIGRATIONS` with the next version number.
+pub fn run_migrations(conn: &mut Connection) -> Result<()> {
+    let current_version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current_version) {
+        println!("🔧 Applying migration {}", migration.version);
+
+        let tx = conn.transaction()?;
+        (migration.run)(&tx)?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}